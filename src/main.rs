@@ -2,6 +2,7 @@ extern crate sdl2;
 
 use std::cmp;
 use std::fs::File;
+use std::io::{self, Write};
 use std::thread::sleep;
 use std::time::{Duration, Instant};
 
@@ -17,10 +18,37 @@ use sdl2::rect::Point;
 use captrs::Capturer;
 use captrs::Bgr8;
 
-use image::{Rgb, RgbImage};
+use clap::Parser;
 
-use engiffen::{engiffen, Image};
-use engiffen::Quantizer::NeuQuant;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::imageops::{resize, FilterType};
+use image::{Delay, Frame as AnimationFrame, Rgb, RgbImage, Rgba, RgbaImage};
+
+use terminal_size::{terminal_size, Width};
+
+/// Capture a region of the screen and save it as an animated GIF.
+#[derive(Parser)]
+struct Cli {
+    /// Recording duration, in seconds
+    #[arg(long, default_value_t = 3)]
+    duration: usize,
+
+    /// Capture frame rate, in frames per second
+    #[arg(long, default_value_t = 10)]
+    fps: usize,
+
+    /// Path to write the resulting GIF to
+    #[arg(long, default_value = "output.gif")]
+    output: String,
+
+    /// Record the whole display instead of interactively selecting a region
+    #[arg(long)]
+    fullscreen: bool,
+
+    /// Skip the terminal preview and write the GIF directly
+    #[arg(long)]
+    no_preview: bool,
+}
 
 struct CaptureContext {
     screen_dimensions: (u32, u32),
@@ -124,16 +152,27 @@ fn get_capture_area(
     }
 
     match selected_corners {
-        (Some(start), Some(end)) => Ok(get_capture_rect(&start, &end)),
+        (Some(start), Some(end)) => {
+            let rect = get_capture_rect(&start, &end);
+            if rect.width() == 0 || rect.height() == 0 {
+                Err(String::from("Capture area must not be empty; drag out a region."))
+            } else {
+                Ok(rect)
+            }
+        }
         _ => Err(String::from("Failed to select area for capture."))
     }
 }
 
-fn get_capture_context() -> Result<CaptureContext, String> {
+fn get_capture_context(fullscreen: bool) -> Result<CaptureContext, String> {
     let sdl_context = sdl2::init()?;
     let video_subsystem = sdl_context.video()?;
     let screen_dimensions = get_screen_dimensions(&video_subsystem)?;
-    let capture_area = get_capture_area(&video_subsystem, &screen_dimensions)?;
+    let capture_area = if fullscreen {
+        Rect::new(0, 0, screen_dimensions.0, screen_dimensions.1)
+    } else {
+        get_capture_area(&video_subsystem, &screen_dimensions)?
+    };
 
     let capture_context = CaptureContext {
         screen_dimensions,
@@ -142,58 +181,318 @@ fn get_capture_context() -> Result<CaptureContext, String> {
     Ok(capture_context)
 }
 
-type Frame = Vec<Bgr8>;
-fn capture_frames(duration: usize, frame_rate: usize) -> Result<Vec<Frame>, String> {
+type RawFrame = Vec<Bgr8>;
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+// A captured frame plus how long it stays on screen (the gap until the next
+// capture; see `compute_frame_delays`), so playback can reproduce the actual
+// capture pacing instead of assuming a uniform rate.
+struct CapturedFrame {
+    data: RawFrame,
+    delay: Duration,
+}
+
+fn capture_frames(duration: usize, frame_rate: usize) -> Result<Vec<CapturedFrame>, String> {
     let mut capturer = Capturer::new(0).unwrap();
 
+    let frame_interval = Duration::from_nanos(NANOS_PER_SEC / frame_rate as u64);
+    let capture_duration = Duration::from_secs(duration as u64);
+    let num_frames = duration * frame_rate + 1;
+
     let capture_start_time = Instant::now();
-    let sleep_time = 1000 / frame_rate;
-    let num_frames = duration / frame_rate + 1;
-    let mut frames: Vec<Frame> = Vec::with_capacity(num_frames);
+    let mut next_deadline = capture_start_time + frame_interval;
+    let mut raw_frames: Vec<RawFrame> = Vec::with_capacity(num_frames);
+    let mut capture_times: Vec<Instant> = Vec::with_capacity(num_frames);
+
     loop {
-        let capture_duration = capture_start_time.elapsed();
-        if capture_duration.as_secs() > (duration as u64) {
+        if capture_start_time.elapsed() >= capture_duration {
             break;
         }
 
         let frame = capturer.capture_frame();
         match frame {
-            Ok(frame_data) => frames.push(frame_data),
+            Ok(frame_data) => {
+                raw_frames.push(frame_data);
+                capture_times.push(Instant::now());
+            }
             _ => {
-                let err_str = format!("Failed to capture frame {}", frames.len());
+                let err_str = format!("Failed to capture frame {}", raw_frames.len());
                 return Err(err_str);
             }
         }
-        sleep(Duration::from_millis(sleep_time.try_into().unwrap()));
+
+        // Sleep only the time remaining until the next deadline; if capture
+        // overran the interval, catch up by skipping the sleep entirely.
+        let now = Instant::now();
+        if now < next_deadline {
+            sleep(next_deadline - now);
+        }
+        next_deadline += frame_interval;
     }
 
+    let delays = compute_frame_delays(&capture_times, frame_interval);
+    let frames = raw_frames.into_iter().zip(delays)
+        .map(|(data, delay)| CapturedFrame { data, delay })
+        .collect();
+
     Ok(frames)
 }
 
-fn convert_frame_to_rgb(frame: Frame, w: u32, h: u32) -> RgbImage {
-    RgbImage::from_fn(w, h, |x, y| {
-        let pixel = frame[(w * y + x) as usize];
+// A frame's delay is how long *it* stays on screen, i.e. the gap until the
+// next frame was captured, not the gap before it arrived. The last frame
+// has no successor to measure against, so it falls back to `fallback`.
+fn compute_frame_delays(capture_times: &[Instant], fallback: Duration) -> Vec<Duration> {
+    capture_times.iter().enumerate()
+        .map(|(i, &time)| {
+            capture_times.get(i + 1)
+                .map(|&next_time| next_time - time)
+                .unwrap_or(fallback)
+        })
+        .collect()
+}
+
+fn convert_frame_to_rgb(frame: RawFrame, w: u32, h: u32, capture_area: &Rect) -> RgbImage {
+    let crop_x = capture_area.x() as u32;
+    let crop_y = capture_area.y() as u32;
+    debug_assert!(
+        crop_x + capture_area.width() <= w && crop_y + capture_area.height() <= h,
+        "capture area must fit within the captured frame",
+    );
+
+    RgbImage::from_fn(capture_area.width(), capture_area.height(), |x, y| {
+        let pixel = frame[(w * (crop_y + y) + (crop_x + x)) as usize];
         Rgb([pixel.r, pixel.g, pixel.b])
     })
 }
 
-fn convert_rgb_to_image(image: RgbImage) -> Image {
-    Image {
-        pixels: image.pixels().map(|p| [p[0], p[1], p[2], 255]).collect(),
-        width: image.width(),
-        height: image.height(),
+// Pixels whose channels all stay within this threshold of the previous
+// frame are considered unchanged background, not part of the dirty rect.
+const DIRTY_PIXEL_THRESHOLD: u8 = 12;
+
+// How many times the GIF should play before stopping.
+enum LoopCount {
+    Infinite,
+    Finite(u16),
+}
+
+// User-facing knobs for `create_gif`, separate from the capture pipeline
+// so output tuning doesn't require touching the capture/diff code.
+struct GifOptions {
+    loop_count: LoopCount,
+    // Speed/quality tradeoff forwarded to `GifEncoder::new_with_speed`: 1
+    // is slowest and highest quality, 30 is fastest and roughest. Because
+    // `image`'s GIF encoder quantizes every frame on its own (see the doc
+    // comment on `build_rgba_frame`), this also governs per-frame color
+    // quantization rather than a single pass shared across frames.
+    quantizer_speed: i32,
+}
+
+impl Default for GifOptions {
+    fn default() -> Self {
+        GifOptions {
+            loop_count: LoopCount::Infinite,
+            quantizer_speed: 10,
+        }
+    }
+}
+
+// A GIF sub-frame: the bounding box of pixels that changed since the
+// previous frame (the whole canvas for the first frame), plus the pixels
+// inside that box.
+struct DirtyFrame {
+    rect: Rect,
+    pixels: RgbImage,
+}
+
+fn pixel_changed(prev: &Rgb<u8>, curr: &Rgb<u8>, threshold: u8) -> bool {
+    prev.0.iter()
+        .zip(curr.0.iter())
+        .any(|(p, c)| (*p as i16 - *c as i16).abs() > threshold as i16)
+}
+
+fn compute_dirty_rect(prev: &RgbImage, curr: &RgbImage, threshold: u8) -> Option<Rect> {
+    let (mut min_x, mut min_y) = (curr.width(), curr.height());
+    let (mut max_x, mut max_y) = (0, 0);
+    let mut found_dirty_pixel = false;
+
+    for (x, y, curr_pixel) in curr.enumerate_pixels() {
+        if pixel_changed(prev.get_pixel(x, y), curr_pixel, threshold) {
+            found_dirty_pixel = true;
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
     }
+
+    if !found_dirty_pixel {
+        return None;
+    }
+    Some(Rect::new(min_x as i32, min_y as i32, max_x - min_x + 1, max_y - min_y + 1))
+}
+
+fn crop_to_rect(image: &RgbImage, rect: &Rect) -> RgbImage {
+    RgbImage::from_fn(rect.width(), rect.height(), |x, y| {
+        *image.get_pixel(rect.x() as u32 + x, rect.y() as u32 + y)
+    })
+}
+
+// An RGB frame paired with how long it should be displayed, carried
+// through from the actual capture timestamps in `CapturedFrame`.
+struct TimedImage {
+    image: RgbImage,
+    delay: Duration,
 }
 
-fn create_gif(frames: &[Image], frame_rate: usize, outfile: &str) -> Result<(), String> {
-    let gif = engiffen(frames, frame_rate, NeuQuant(4)).unwrap(); // need to handle error
-    let mut output = File::create(outfile).unwrap(); // need to handle error
-    gif.write(&mut output).unwrap();
+// Diffs consecutive frames down to the rectangle that actually changed, so
+// `create_gif` can emit small sub-frames instead of re-encoding the whole
+// canvas every time.
+fn diff_frames(images: &[&RgbImage]) -> Vec<DirtyFrame> {
+    let mut dirty_frames = Vec::with_capacity(images.len());
+    let mut previous: Option<&RgbImage> = None;
+
+    for &image in images {
+        let rect = match previous {
+            None => Rect::new(0, 0, image.width(), image.height()),
+            Some(prev_image) => {
+                compute_dirty_rect(prev_image, image, DIRTY_PIXEL_THRESHOLD)
+                    // Nothing changed; still emit a tiny frame to preserve timing.
+                    .unwrap_or_else(|| Rect::new(0, 0, 1, 1))
+            }
+        };
+        dirty_frames.push(DirtyFrame { pixels: crop_to_rect(image, &rect), rect });
+        previous = Some(image);
+    }
+
+    dirty_frames
+}
+
+// Builds the RGBA sub-image `image::Frame::from_parts` needs for a dirty
+// frame: pixels unchanged from `previous` get alpha 0, so `GifEncoder`'s
+// own transparency detection (it treats near-zero alpha as the
+// transparent index when it quantizes the frame) lets the unchanged
+// background show through instead of redrawing it.
+//
+// Two things are out of reach here, because the `image` crate's GIF path
+// has no public hook for them:
+//   - `image::Frame` (see `image::Frame::from_parts`) carries only a
+//     buffer/left/top/delay — there is no disposal-method field, so the
+//     disposal method of delta frames is not set here; `GifEncoder` picks
+//     its own disposal.
+//   - `GifEncoder::encode_frame` quantizes each frame independently
+//     (there's no API to hand it one palette shared across frames), so
+//     frames do not share a single color table.
+fn build_rgba_frame(dirty_frame: &DirtyFrame, previous: Option<&RgbImage>) -> RgbaImage {
+    RgbaImage::from_fn(dirty_frame.pixels.width(), dirty_frame.pixels.height(), |x, y| {
+        let pixel = dirty_frame.pixels.get_pixel(x, y);
+        let unchanged = previous.is_some_and(|prev_frame| {
+            !pixel_changed(
+                prev_frame.get_pixel(dirty_frame.rect.x() as u32 + x, dirty_frame.rect.y() as u32 + y),
+                pixel,
+                DIRTY_PIXEL_THRESHOLD,
+            )
+        });
+        let alpha = if unchanged { 0 } else { 255 };
+        Rgba([pixel[0], pixel[1], pixel[2], alpha])
+    })
+}
+
+const DEFAULT_PREVIEW_COLUMNS: u32 = 80;
+
+// Downscales to the terminal's column width, keeping aspect ratio. Each
+// character cell renders two pixel rows (see `render_half_block_frame`),
+// so the scaled height is twice the target row count.
+fn downscale_for_terminal(image: &RgbImage, columns: u32) -> RgbImage {
+    let aspect_ratio = image.height() as f32 / image.width() as f32;
+    let rows = ((columns as f32) * aspect_ratio / 2.0).round().max(1.0) as u32;
+    resize(image, columns, rows * 2, FilterType::Triangle)
+}
+
+// Renders an image as one line per pixel-row-pair: the Unicode upper-half
+// block with the top pixel as the truecolor foreground and the bottom
+// pixel as the truecolor background, so a single character cell shows two
+// pixels without the vertical squash a single block-per-pixel cell would have.
+fn render_half_block_frame(image: &RgbImage) -> String {
+    let mut rendered = String::new();
+    let mut y = 0;
+    while y + 1 < image.height() {
+        for x in 0..image.width() {
+            let top = image.get_pixel(x, y);
+            let bottom = image.get_pixel(x, y + 1);
+            rendered.push_str(&format!(
+                "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m\u{2580}",
+                top[0], top[1], top[2], bottom[0], bottom[1], bottom[2],
+            ));
+        }
+        rendered.push_str("\x1b[0m\n");
+        y += 2;
+    }
+    rendered
+}
+
+// Plays the captured frames back in the terminal at the recorded pacing so
+// the user can confirm the recording before `create_gif` writes it to disk.
+fn preview_frames(frames: &[TimedImage]) -> Result<(), String> {
+    let columns = terminal_size()
+        .map(|(Width(columns), _)| columns as u32)
+        .unwrap_or(DEFAULT_PREVIEW_COLUMNS);
+
+    let mut stdout = io::stdout();
+    print!("\x1b[2J");
+    for frame in frames {
+        let scaled = downscale_for_terminal(&frame.image, columns);
+        print!("\x1b[H{}", render_half_block_frame(&scaled));
+        stdout.flush().map_err(|e| e.to_string())?;
+        sleep(frame.delay);
+    }
+    Ok(())
+}
+
+fn create_gif(frames: &[TimedImage], options: &GifOptions, outfile: &str) -> Result<(), String> {
+    if frames.is_empty() {
+        return Err(String::from("No frames to encode"));
+    }
+
+    let images: Vec<&RgbImage> = frames.iter().map(|frame| &frame.image).collect();
+    let dirty_frames = diff_frames(&images);
+
+    let output = File::create(outfile).map_err(|e| e.to_string())?;
+    let mut encoder = GifEncoder::new_with_speed(output, options.quantizer_speed);
+    let repeat = match options.loop_count {
+        LoopCount::Infinite => Repeat::Infinite,
+        LoopCount::Finite(count) => Repeat::Finite(count),
+    };
+    encoder.set_repeat(repeat).map_err(|e| e.to_string())?;
+
+    let mut previous: Option<&RgbImage> = None;
+    for (dirty_frame, frame) in dirty_frames.iter().zip(frames) {
+        let rgba = build_rgba_frame(dirty_frame, previous);
+        let delay = Delay::from_saturating_duration(frame.delay);
+        let animation_frame = AnimationFrame::from_parts(
+            rgba,
+            dirty_frame.rect.x() as u32,
+            dirty_frame.rect.y() as u32,
+            delay,
+        );
+        encoder.encode_frame(animation_frame).map_err(|e| e.to_string())?;
+        previous = Some(&frame.image);
+    }
+
     Ok(())
 }
 
 fn main() -> Result<(), String> {
-    let capture_context = get_capture_context()?;
+    let cli = Cli::parse();
+
+    if cli.fps == 0 {
+        return Err(String::from("--fps must be greater than 0"));
+    }
+    if cli.duration == 0 {
+        return Err(String::from("--duration must be greater than 0"));
+    }
+
+    let capture_context = get_capture_context(cli.fullscreen)?;
     let screen_dimensions = capture_context.screen_dimensions;
     let capture_area = capture_context.capture_area;
 
@@ -202,16 +501,76 @@ fn main() -> Result<(), String> {
 
     sleep(Duration::from_secs(1));
 
-    let capture_duration = 3;
-    let frame_rate = 10;
+    let show_preview = !cli.no_preview;
 
-    let frames = capture_frames(capture_duration, frame_rate)?;
+    let frames = capture_frames(cli.duration, cli.fps)?;
     println!("Captured {} frames", frames.len());
 
     let gif_images: Vec<_> = frames.into_iter()
-        .map(|f| convert_frame_to_rgb(f, screen_dimensions.0, screen_dimensions.1))
-        .map(convert_rgb_to_image)
+        .map(|f| TimedImage {
+            image: convert_frame_to_rgb(f.data, screen_dimensions.0, screen_dimensions.1, &capture_area),
+            delay: f.delay,
+        })
         .collect();
 
-    create_gif(&gif_images, frame_rate, "output.gif")
+    if show_preview {
+        preview_frames(&gif_images)?;
+    }
+
+    create_gif(&gif_images, &GifOptions::default(), &cli.output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid_image(width: u32, height: u32, color: [u8; 3]) -> RgbImage {
+        RgbImage::from_fn(width, height, |_, _| Rgb(color))
+    }
+
+    #[test]
+    fn compute_dirty_rect_finds_the_bounding_box_of_changed_pixels() {
+        let prev = solid_image(4, 4, [0, 0, 0]);
+        let mut curr = solid_image(4, 4, [0, 0, 0]);
+        curr.put_pixel(1, 1, Rgb([255, 255, 255]));
+        curr.put_pixel(2, 3, Rgb([255, 255, 255]));
+
+        let rect = compute_dirty_rect(&prev, &curr, DIRTY_PIXEL_THRESHOLD).unwrap();
+        assert_eq!(rect, Rect::new(1, 1, 2, 3));
+    }
+
+    #[test]
+    fn compute_dirty_rect_is_none_when_nothing_changed() {
+        let prev = solid_image(4, 4, [10, 10, 10]);
+        let curr = solid_image(4, 4, [10, 10, 10]);
+
+        assert!(compute_dirty_rect(&prev, &curr, DIRTY_PIXEL_THRESHOLD).is_none());
+    }
+
+    #[test]
+    fn diff_frames_emits_a_1x1_frame_when_nothing_changed() {
+        let first = solid_image(4, 4, [5, 5, 5]);
+        let second = first.clone();
+        let images: Vec<&RgbImage> = vec![&first, &second];
+
+        let dirty_frames = diff_frames(&images);
+
+        assert_eq!(dirty_frames[0].rect, Rect::new(0, 0, 4, 4));
+        assert_eq!(dirty_frames[1].rect, Rect::new(0, 0, 1, 1));
+    }
+
+    #[test]
+    fn compute_frame_delays_falls_back_for_the_last_frame() {
+        let t0 = Instant::now();
+        let t1 = t0 + Duration::from_millis(16);
+        let t2 = t1 + Duration::from_millis(20);
+        let fallback = Duration::from_millis(100);
+
+        let delays = compute_frame_delays(&[t0, t1, t2], fallback);
+
+        assert_eq!(
+            delays,
+            vec![Duration::from_millis(16), Duration::from_millis(20), fallback],
+        );
+    }
 }